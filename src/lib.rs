@@ -1,12 +1,205 @@
 use std::error::Error;
 use std::fs::{File, Metadata};
-use std::io::{BufReader, Read, ErrorKind, Seek};
+use std::io::{BufReader, BufWriter, Read, Write, ErrorKind};
 use std::mem::size_of;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long to sleep between retries while following a capture that's still being written to.
+const FOLLOW_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Magic number of a little-endian capture with microsecond-resolution timestamps.
+const MAGIC_MICRO_LE: u32 = 0xA1B2C3D4;
+/// Magic number of a big-endian (byte-swapped) capture with microsecond-resolution timestamps.
+const MAGIC_MICRO_BE: u32 = 0xD4C3B2A1;
+/// Magic number of a little-endian capture with nanosecond-resolution timestamps.
+const MAGIC_NANO_LE: u32 = 0xA1B23C4D;
+/// Magic number of a big-endian (byte-swapped) capture with nanosecond-resolution timestamps.
+const MAGIC_NANO_BE: u32 = 0x4D3CB2A1;
+
+/// Byte order the rest of the file's fields are encoded in, resolved from the magic number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+/// Link-layer header type carried in the global header's `network` field.
+///
+/// Covers the values seen in practice; anything else is kept as `Unknown` rather than rejected,
+/// since new link types are registered more often than this crate is updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linktype {
+    Null,
+    Ethernet,
+    Ieee802_11,
+    LinuxSll,
+    Raw,
+    Unknown(u32),
+}
+
+impl Linktype {
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            0 => Linktype::Null,
+            1 => Linktype::Ethernet,
+            101 => Linktype::Raw,
+            105 => Linktype::Ieee802_11,
+            113 => Linktype::LinuxSll,
+            other => Linktype::Unknown(other),
+        }
+    }
+
+    pub fn as_u32(&self) -> u32 {
+        match self {
+            Linktype::Null => 0,
+            Linktype::Ethernet => 1,
+            Linktype::Raw => 101,
+            Linktype::Ieee802_11 => 105,
+            Linktype::LinuxSll => 113,
+            Linktype::Unknown(value) => *value,
+        }
+    }
+}
+
+/// Default ceiling on a single packet's captured length, independent of the file's claimed
+/// `snaplen`, so a corrupt or hostile `incl_len` can't trigger a multi-gigabyte allocation.
+pub const DEFAULT_MAX_PACKET_LEN: u32 = 1_610_612_736; // 1.5 GiB
+
+/// Size of `PcapReader`'s read buffer at open time, before any packet has been seen. Kept small
+/// and independent of `snaplen`/`max_packet_len` so opening a file never itself allocates more
+/// than this; `next` grows the buffer on demand for any in-bounds `incl_len` above it.
+const INITIAL_BUFFER_LEN: u32 = 64 * 1024;
+
+/// Errors produced while parsing or writing a pcap capture.
+#[derive(Debug)]
+pub enum PcapError {
+    /// The global header was too short to read.
+    InvalidFileHeader,
+    /// The magic number didn't match any of the four values libpcap writers use.
+    UnknownMagic(u32),
+    /// A packet's `incl_len` exceeded the configured maximum or the capture's `snaplen`.
+    PacketTooLarge { incl_len: u32, limit: u32 },
+    /// The stream ended partway through a record.
+    Truncated,
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for PcapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PcapError::InvalidFileHeader => write!(f, "invalid or truncated pcap global header"),
+            PcapError::UnknownMagic(magic) => write!(f, "unknown pcap magic number: 0x{:08X}", magic),
+            PcapError::PacketTooLarge { incl_len, limit } => {
+                write!(f, "packet length {} exceeds limit {}", incl_len, limit)
+            }
+            PcapError::Truncated => write!(f, "truncated pcap record"),
+            PcapError::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for PcapError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PcapError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PcapError {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            ErrorKind::UnexpectedEof => PcapError::Truncated,
+            _ => PcapError::Io(error),
+        }
+    }
+}
+
+fn read_u16(buf: &[u8], order: Endianness) -> u16 {
+    match order {
+        Endianness::Little => u16::from_le_bytes(buf.try_into().unwrap()),
+        Endianness::Big => u16::from_be_bytes(buf.try_into().unwrap()),
+    }
+}
+
+fn read_u32(buf: &[u8], order: Endianness) -> u32 {
+    match order {
+        Endianness::Little => u32::from_le_bytes(buf.try_into().unwrap()),
+        Endianness::Big => u32::from_be_bytes(buf.try_into().unwrap()),
+    }
+}
+
+fn read_i32(buf: &[u8], order: Endianness) -> i32 {
+    match order {
+        Endianness::Little => i32::from_le_bytes(buf.try_into().unwrap()),
+        Endianness::Big => i32::from_be_bytes(buf.try_into().unwrap()),
+    }
+}
+
+fn write_u16(buf: &mut [u8], value: u16, order: Endianness) {
+    buf.copy_from_slice(&match order {
+        Endianness::Little => value.to_le_bytes(),
+        Endianness::Big => value.to_be_bytes(),
+    });
+}
+
+fn write_u32(buf: &mut [u8], value: u32, order: Endianness) {
+    buf.copy_from_slice(&match order {
+        Endianness::Little => value.to_le_bytes(),
+        Endianness::Big => value.to_be_bytes(),
+    });
+}
+
+fn write_i32(buf: &mut [u8], value: i32, order: Endianness) {
+    buf.copy_from_slice(&match order {
+        Endianness::Little => value.to_le_bytes(),
+        Endianness::Big => value.to_be_bytes(),
+    });
+}
+
+fn decode_packet_header(buffer: &[u8], byte_order: Endianness) -> PacketHeader {
+    PacketHeader {
+        ts_sec: read_u32(&buffer[0..4], byte_order),
+        ts_usec: read_u32(&buffer[4..8], byte_order),
+        incl_len: read_u32(&buffer[8..12], byte_order),
+        orig_len: read_u32(&buffer[12..=15], byte_order),
+    }
+}
+
+/// Fills `buf` completely, or reports that the stream ended before `buf` started filling.
+///
+/// Returns `Ok(true)` once `buf` is full, `Ok(false)` if the stream ended cleanly before any
+/// byte was read (a legitimate boundary between records), or an `UnexpectedEof` error if it
+/// ended partway through (a truncated record).
+///
+/// When `follow` is set, an empty read never signals "no more data" — the already-filled
+/// prefix of `buf` is kept and the read is retried after [`FOLLOW_RETRY_INTERVAL`], so a
+/// capture that's still being appended to is tailed rather than treated as finished or corrupt.
+fn fill_at_boundary<R: Read>(reader: &mut R, buf: &mut [u8], follow: bool) -> std::io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if follow => thread::sleep(FOLLOW_RETRY_INTERVAL),
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(std::io::Error::new(ErrorKind::UnexpectedEof, "truncated pcap record")),
+            Ok(n) => filled += n,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(true)
+}
 
 pub struct PcapFile {
     pub global_header: PcapHeader,
     pub packets: Vec<Packet>,
     pub metadata: Metadata,
+    /// Byte order every field after the magic number is encoded in.
+    pub byte_order: Endianness,
+    /// Whether `ts_usec` on each packet holds nanoseconds rather than microseconds.
+    pub nanosecond_resolution: bool,
 }
 
 impl std::fmt::Debug for PcapFile {
@@ -15,6 +208,8 @@ impl std::fmt::Debug for PcapFile {
             .field("global_header", &self.global_header)
             .field("packets (count): ", &self.packets.len())
             .field("metadata", &self.metadata)
+            .field("byte_order", &self.byte_order)
+            .field("nanosecond_resolution", &self.nanosecond_resolution)
             .finish()
     }
 }
@@ -48,6 +243,38 @@ impl std::fmt::Debug for PcapHeader {
 pub struct Packet {
     header: PacketHeader,
     data: PacketData,
+    /// Whether `header.ts_usec` holds nanoseconds rather than microseconds, carried over from
+    /// the file this packet was read from so `timestamp` doesn't need it passed in.
+    nanosecond_resolution: bool,
+}
+
+impl Packet {
+    /// The bytes actually captured, i.e. the first `incl_len` bytes of the packet on the wire.
+    pub fn data(&self) -> &[u8] {
+        &self.data.data
+    }
+
+    /// The packet's length on the wire, which may exceed `data().len()` if it was truncated.
+    pub fn orig_len(&self) -> u32 {
+        self.header.orig_len
+    }
+
+    /// Whether the capture stopped short of the full packet, i.e. `data()` is missing bytes
+    /// a decoder would need to parse the full payload.
+    pub fn is_truncated(&self) -> bool {
+        self.header.incl_len < self.header.orig_len
+    }
+
+    /// The capture time as a `SystemTime`, rather than raw `ts_sec`/`ts_usec` fields callers
+    /// would otherwise have to re-derive the epoch math for.
+    pub fn timestamp(&self) -> SystemTime {
+        let sub_second = if self.nanosecond_resolution {
+            Duration::from_nanos(self.header.ts_usec as u64)
+        } else {
+            Duration::from_micros(self.header.ts_usec as u64)
+        };
+        UNIX_EPOCH + Duration::from_secs(self.header.ts_sec as u64) + sub_second
+    }
 }
 
 #[derive(Default, Debug)]
@@ -64,77 +291,212 @@ struct PacketData {
 }
 
 impl PcapFile {
-    pub fn read_file(path: &str) -> Result<Self, Box<dyn Error>> {
+    pub fn read_file(path: &str) -> Result<Self, PcapError> {
         let file = File::open(path)?;
         let metadata = file.metadata()?;
-        let mut reader = BufReader::new(file);
-        let file_header = dbg!(Self::read_file_header(&mut reader)?);
-        let packets = Self::read_packets(&mut reader)?;
+        let reader = BufReader::new(file);
+        let mut pcap_reader = PcapReader::new(reader)?;
+
+        let mut packets = Vec::new();
+        for packet in pcap_reader.by_ref() {
+            packets.push(packet?);
+        }
+
         Ok(Self {
-            global_header: file_header,
+            global_header: pcap_reader.global_header,
             packets,
             metadata,
+            byte_order: pcap_reader.byte_order,
+            nanosecond_resolution: pcap_reader.nanosecond_resolution,
         })
     }
 
-    fn read_file_header<R: Read>(file: &mut R) -> Result<PcapHeader, Box<dyn Error>> {
+    fn read_file_header<R: Read>(file: &mut R) -> Result<(PcapHeader, Endianness, bool), PcapError> {
         let mut file_header = PcapHeader::default();
         let mut buffer = [0u8; size_of::<PcapHeader>()];
 
-        file.read_exact(&mut buffer)?;
-        // The reading application will read either 0xa1b2c3d4 (identical) or 0xd4c3b2a1 (swapped)
-        file_header.magic_number = u32::from_le_bytes(buffer[0..4].try_into().unwrap());
-        file_header.version_major = u16::from_le_bytes(buffer[4..6].try_into().unwrap());
-        file_header.version_minor = u16::from_le_bytes(buffer[6..8].try_into().unwrap());
-        file_header.thiszone = i32::from_le_bytes(buffer[8..12].try_into().unwrap());
-        file_header.sigfigs = u32::from_le_bytes(buffer[12..16].try_into().unwrap());
-        file_header.snaplen = u32::from_le_bytes(buffer[16..20].try_into().unwrap());
-        file_header.network = u32::from_le_bytes(buffer[20..=23].try_into().unwrap());
-
-        Ok(file_header)
-    }
-
-    fn read_packets<R: Read + Seek>(file: &mut R) -> Result<Vec<Packet>, Box<dyn Error>> {
-        let mut packets: Vec<Packet> = vec![];
-        // Just example. Maybe some functional tricks could be nicer i.e. packets = collect(...)
-        loop {
-            let packet = Self::read_packet(file);
-
-            match packet {
-                Ok(packet) => packets.push(packet),
-                Err(error) => match error.kind() { 
-                    ErrorKind::UnexpectedEof => {
-                        break;
-                    },
-                    _ =>{ return Err(Box::new(error)); }
-                }
-            }
+        file.read_exact(&mut buffer).map_err(|error| match error.kind() {
+            ErrorKind::UnexpectedEof => PcapError::InvalidFileHeader,
+            _ => PcapError::Io(error),
+        })?;
+        // The magic number is always decoded as little-endian bytes off disk; which of the
+        // four known values it matches tells us the byte order and timestamp resolution of
+        // every field that follows.
+        let magic_number = u32::from_le_bytes(buffer[0..4].try_into().unwrap());
+        let (byte_order, nanosecond_resolution) = match magic_number {
+            MAGIC_MICRO_LE => (Endianness::Little, false),
+            MAGIC_MICRO_BE => (Endianness::Big, false),
+            MAGIC_NANO_LE => (Endianness::Little, true),
+            MAGIC_NANO_BE => (Endianness::Big, true),
+            _ => return Err(PcapError::UnknownMagic(magic_number)),
+        };
+
+        file_header.magic_number = magic_number;
+        file_header.version_major = read_u16(&buffer[4..6], byte_order);
+        file_header.version_minor = read_u16(&buffer[6..8], byte_order);
+        file_header.thiszone = read_i32(&buffer[8..12], byte_order);
+        file_header.sigfigs = read_u32(&buffer[12..16], byte_order);
+        file_header.snaplen = read_u32(&buffer[16..20], byte_order);
+        file_header.network = read_u32(&buffer[20..=23], byte_order);
+
+        Ok((file_header, byte_order, nanosecond_resolution))
+    }
+
+    /// The link-layer header type packets in this capture start with.
+    pub fn linktype(&self) -> Linktype {
+        Linktype::from_u32(self.global_header.network)
+    }
+
+    /// Re-emits a loaded capture to `path`, using the byte order and resolution it was read with.
+    pub fn write_file(&self, path: &str) -> Result<(), PcapError> {
+        let file = File::create(path)?;
+        let mut writer = PcapWriter::new(BufWriter::new(file), &self.global_header)?;
+        for packet in &self.packets {
+            writer.write_packet(packet)?;
         }
-        Ok(packets)
+        Ok(())
     }
+}
 
-    fn read_packet<R: Read>(file: &mut R) -> Result<Packet, std::io::Error> {
-        let header = Self::read_packet_header(file)?;
+/// Parses packets one at a time from any reader, instead of loading the whole capture into
+/// memory like [`PcapFile::read_file`] does.
+pub struct PcapReader<R: Read> {
+    reader: R,
+    global_header: PcapHeader,
+    byte_order: Endianness,
+    nanosecond_resolution: bool,
+    /// Read target reused across calls to `next`; it is only reallocated when a packet is larger
+    /// than any seen so far. Each yielded `Packet` still gets its own copy of the captured bytes.
+    buffer: Vec<u8>,
+    max_packet_len: u32,
+    follow: bool,
+}
 
-        let mut data = Vec::<u8>::new();
-        data.resize(header.incl_len as usize, 0);
-        file.read_exact(&mut data)?;
-        Ok(Packet {
-            header,
-            data: PacketData { data },
+impl<R: Read> PcapReader<R> {
+    pub fn new(mut reader: R) -> Result<Self, PcapError> {
+        let (global_header, byte_order, nanosecond_resolution) = PcapFile::read_file_header(&mut reader)?;
+        // `snaplen` comes straight from the (unvalidated) file, so it must not drive the initial
+        // allocation size: start small and let `next` grow the buffer on demand for any
+        // in-bounds `incl_len`, which keeps a hostile snaplen from reserving gigabytes up front.
+        let buffer = vec![0u8; global_header.snaplen.min(INITIAL_BUFFER_LEN) as usize];
+        Ok(Self {
+            reader,
+            global_header,
+            byte_order,
+            nanosecond_resolution,
+            buffer,
+            max_packet_len: DEFAULT_MAX_PACKET_LEN,
+            follow: false,
         })
     }
 
-    fn read_packet_header<R: Read>(file: &mut R) -> Result<PacketHeader, std::io::Error> {
-        let mut packet_header = PacketHeader::default();
-        let mut buffer = [0u8; size_of::<PacketHeader>()];
+    /// Overrides the ceiling on a single packet's `incl_len`, beyond which [`PcapError::PacketTooLarge`]
+    /// is reported instead of attempting to allocate and read that many bytes.
+    pub fn with_max_packet_len(mut self, max_packet_len: u32) -> Self {
+        self.max_packet_len = max_packet_len;
+        self
+    }
 
-        file.read_exact(&mut buffer)?;
-        packet_header.ts_sec = u32::from_le_bytes(buffer[0..4].try_into().unwrap());
-        packet_header.ts_usec = u32::from_le_bytes(buffer[4..8].try_into().unwrap());
-        packet_header.incl_len = u32::from_le_bytes(buffer[8..12].try_into().unwrap());
-        packet_header.orig_len = u32::from_le_bytes(buffer[12..=15].try_into().unwrap());
+    /// When `follow` is `true`, hitting the end of the stream no longer ends iteration — `next`
+    /// instead blocks, retrying until a capture tool that's still appending to the file writes
+    /// more data, like `tail -f`.
+    pub fn with_follow(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        self
+    }
 
-        Ok(packet_header)
+    pub fn global_header(&self) -> &PcapHeader {
+        &self.global_header
+    }
+
+    pub fn byte_order(&self) -> Endianness {
+        self.byte_order
+    }
+
+    pub fn nanosecond_resolution(&self) -> bool {
+        self.nanosecond_resolution
+    }
+}
+
+impl<R: Read> Iterator for PcapReader<R> {
+    type Item = Result<Packet, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut header_buffer = [0u8; size_of::<PacketHeader>()];
+        match fill_at_boundary(&mut self.reader, &mut header_buffer, self.follow) {
+            Ok(false) => return None,
+            Err(error) => return Some(Err(error.into())),
+            Ok(true) => {}
+        }
+        let header = decode_packet_header(&header_buffer, self.byte_order);
+
+        let limit = self.max_packet_len.min(self.global_header.snaplen);
+        if header.incl_len > limit {
+            return Some(Err(PcapError::PacketTooLarge { incl_len: header.incl_len, limit }));
+        }
+
+        let incl_len = header.incl_len as usize;
+        if self.buffer.len() < incl_len {
+            self.buffer.resize(incl_len, 0);
+        }
+        match fill_at_boundary(&mut self.reader, &mut self.buffer[..incl_len], self.follow) {
+            // The header was read in full, so an end of stream before the body is mid-record.
+            Ok(false) => return Some(Err(PcapError::Truncated)),
+            Err(error) => return Some(Err(error.into())),
+            Ok(true) => {}
+        }
+
+        Some(Ok(Packet {
+            header,
+            data: PacketData { data: self.buffer[..incl_len].to_vec() },
+            nanosecond_resolution: self.nanosecond_resolution,
+        }))
+    }
+}
+
+/// Serializes a global header and packets to a writer, mirroring [`PcapReader`].
+pub struct PcapWriter<W: Write> {
+    writer: W,
+    byte_order: Endianness,
+}
+
+impl<W: Write> PcapWriter<W> {
+    /// Writes the 24-byte global header, deriving the byte order to use for every subsequent
+    /// field from `header.magic_number`.
+    pub fn new(mut writer: W, header: &PcapHeader) -> Result<Self, PcapError> {
+        let byte_order = match header.magic_number {
+            MAGIC_MICRO_LE | MAGIC_NANO_LE => Endianness::Little,
+            MAGIC_MICRO_BE | MAGIC_NANO_BE => Endianness::Big,
+            _ => return Err(PcapError::UnknownMagic(header.magic_number)),
+        };
+
+        let mut buffer = [0u8; size_of::<PcapHeader>()];
+        buffer[0..4].copy_from_slice(&header.magic_number.to_le_bytes());
+        write_u16(&mut buffer[4..6], header.version_major, byte_order);
+        write_u16(&mut buffer[6..8], header.version_minor, byte_order);
+        write_i32(&mut buffer[8..12], header.thiszone, byte_order);
+        write_u32(&mut buffer[12..16], header.sigfigs, byte_order);
+        write_u32(&mut buffer[16..20], header.snaplen, byte_order);
+        write_u32(&mut buffer[20..24], header.network, byte_order);
+        writer.write_all(&buffer)?;
+
+        Ok(Self { writer, byte_order })
+    }
+
+    /// Emits the 16-byte packet header followed by `packet.data().len()` bytes of payload.
+    ///
+    /// The length field is derived from `packet.data()` rather than trusted from
+    /// `packet.header.incl_len`, so a hand-built or mutated `Packet` where the two disagree
+    /// can't produce a file whose declared and actual record lengths mismatch.
+    pub fn write_packet(&mut self, packet: &Packet) -> Result<(), PcapError> {
+        let incl_len = packet.data.data.len() as u32;
+        let mut buffer = [0u8; size_of::<PacketHeader>()];
+        write_u32(&mut buffer[0..4], packet.header.ts_sec, self.byte_order);
+        write_u32(&mut buffer[4..8], packet.header.ts_usec, self.byte_order);
+        write_u32(&mut buffer[8..12], incl_len, self.byte_order);
+        write_u32(&mut buffer[12..16], packet.header.orig_len, self.byte_order);
+        self.writer.write_all(&buffer)?;
+        self.writer.write_all(&packet.data.data)?;
+        Ok(())
     }
 }
@@ -0,0 +1,49 @@
+use std::io::Cursor;
+
+use rust_pcap::{Endianness, PcapReader};
+
+/// Global header + a single packet, written as big-endian bytes (magic 0xD4C3B2A1 means the
+/// file was written on a big-endian host, i.e. everything after the magic is byte-swapped
+/// relative to little-endian).
+fn big_endian_capture() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&0xD4C3B2A1u32.to_le_bytes()); // magic is always read as-is
+    bytes.extend_from_slice(&2u16.to_be_bytes()); // version_major
+    bytes.extend_from_slice(&4u16.to_be_bytes()); // version_minor
+    bytes.extend_from_slice(&0i32.to_be_bytes()); // thiszone
+    bytes.extend_from_slice(&0u32.to_be_bytes()); // sigfigs
+    bytes.extend_from_slice(&262144u32.to_be_bytes()); // snaplen
+    bytes.extend_from_slice(&1u32.to_be_bytes()); // network (Ethernet)
+
+    let payload = [0xDEu8, 0xAD, 0xBE, 0xEF];
+    bytes.extend_from_slice(&1690000000u32.to_be_bytes()); // ts_sec
+    bytes.extend_from_slice(&123456u32.to_be_bytes()); // ts_usec
+    bytes.extend_from_slice(&(payload.len() as u32).to_be_bytes()); // incl_len
+    bytes.extend_from_slice(&(payload.len() as u32).to_be_bytes()); // orig_len
+    bytes.extend_from_slice(&payload);
+
+    bytes
+}
+
+#[test]
+fn decodes_big_endian_global_header() {
+    let reader = PcapReader::new(Cursor::new(big_endian_capture())).unwrap();
+
+    assert_eq!(Endianness::Big, reader.byte_order());
+    assert!(!reader.nanosecond_resolution());
+    assert_eq!(2, reader.global_header().version_major);
+    assert_eq!(4, reader.global_header().version_minor);
+    assert_eq!(262144, reader.global_header().snaplen);
+    assert_eq!(1, reader.global_header().network);
+}
+
+#[test]
+fn decodes_big_endian_packet_fields() {
+    let mut reader = PcapReader::new(Cursor::new(big_endian_capture())).unwrap();
+
+    let packet = reader.next().unwrap().unwrap();
+    assert_eq!(&[0xDE, 0xAD, 0xBE, 0xEF], packet.data());
+    assert_eq!(4, packet.orig_len());
+    assert!(!packet.is_truncated());
+    assert!(reader.next().is_none());
+}
@@ -0,0 +1,93 @@
+use std::io::Cursor;
+
+use rust_pcap::{PcapError, PcapReader};
+
+fn global_header_bytes(snaplen: u32) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&0xA1B2C3D4u32.to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&4u16.to_le_bytes());
+    bytes.extend_from_slice(&0i32.to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&snaplen.to_le_bytes());
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    bytes
+}
+
+#[test]
+fn rejects_unknown_magic_number() {
+    let mut bytes = vec![0u8; 24];
+    bytes[0..4].copy_from_slice(&0xDEADBEEFu32.to_le_bytes());
+
+    match PcapReader::new(Cursor::new(bytes)) {
+        Err(PcapError::UnknownMagic(0xDEADBEEF)) => {}
+        Ok(_) => panic!("expected UnknownMagic(0xDEADBEEF), got Ok"),
+        Err(other) => panic!("expected UnknownMagic(0xDEADBEEF), got {:?}", other),
+    }
+}
+
+#[test]
+fn rejects_packet_larger_than_snaplen() {
+    let mut bytes = global_header_bytes(16);
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // ts_sec
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // ts_usec
+    bytes.extend_from_slice(&64u32.to_le_bytes()); // incl_len > snaplen
+    bytes.extend_from_slice(&64u32.to_le_bytes()); // orig_len
+
+    let mut reader = PcapReader::new(Cursor::new(bytes)).unwrap();
+    match reader.next() {
+        Some(Err(PcapError::PacketTooLarge { incl_len: 64, limit: 16 })) => {}
+        other => panic!("expected PacketTooLarge {{ incl_len: 64, limit: 16 }}, got {:?}", other),
+    }
+}
+
+#[test]
+fn rejects_packet_larger_than_configured_max() {
+    let mut bytes = global_header_bytes(65535);
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&1024u32.to_le_bytes()); // incl_len, within snaplen...
+    bytes.extend_from_slice(&1024u32.to_le_bytes());
+
+    let mut reader = PcapReader::new(Cursor::new(bytes)).unwrap().with_max_packet_len(512); // ...but above our limit
+    match reader.next() {
+        Some(Err(PcapError::PacketTooLarge { incl_len: 1024, limit: 512 })) => {}
+        other => panic!("expected PacketTooLarge {{ incl_len: 1024, limit: 512 }}, got {:?}", other),
+    }
+}
+
+#[test]
+fn clean_eof_between_records_ends_iteration() {
+    let mut bytes = global_header_bytes(65535);
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&3u32.to_le_bytes());
+    bytes.extend_from_slice(&3u32.to_le_bytes());
+    bytes.extend_from_slice(&[1, 2, 3]);
+
+    let mut reader = PcapReader::new(Cursor::new(bytes)).unwrap();
+    assert!(reader.next().unwrap().is_ok());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn truncated_record_header_is_an_error_not_a_clean_eof() {
+    let mut bytes = global_header_bytes(65535);
+    bytes.extend_from_slice(&[0u8; 10]); // a 16-byte packet header, cut off after 10 bytes
+
+    let mut reader = PcapReader::new(Cursor::new(bytes)).unwrap();
+    assert!(matches!(reader.next(), Some(Err(PcapError::Truncated))));
+}
+
+#[test]
+fn truncated_record_body_is_an_error() {
+    let mut bytes = global_header_bytes(65535);
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&5u32.to_le_bytes()); // incl_len claims 5 bytes...
+    bytes.extend_from_slice(&5u32.to_le_bytes());
+    bytes.extend_from_slice(&[1, 2]); // ...but only 2 are present
+
+    let mut reader = PcapReader::new(Cursor::new(bytes)).unwrap();
+    assert!(matches!(reader.next(), Some(Err(PcapError::Truncated))));
+}
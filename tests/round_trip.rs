@@ -0,0 +1,39 @@
+use std::io::Cursor;
+
+use rust_pcap::{PcapReader, PcapWriter};
+
+/// Little-endian global header + two packets, one of them empty.
+fn sample_capture() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&0xA1B2C3D4u32.to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&4u16.to_le_bytes());
+    bytes.extend_from_slice(&0i32.to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&65535u32.to_le_bytes());
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+
+    for payload in [&b"hello"[..], &b""[..]] {
+        bytes.extend_from_slice(&1690000000u32.to_le_bytes());
+        bytes.extend_from_slice(&42u32.to_le_bytes());
+        bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(payload);
+    }
+
+    bytes
+}
+
+#[test]
+fn read_then_write_is_byte_identical() {
+    let original = sample_capture();
+    let mut reader = PcapReader::new(Cursor::new(original.clone())).unwrap();
+    let mut output = Vec::new();
+    let mut writer = PcapWriter::new(&mut output, reader.global_header()).unwrap();
+
+    for packet in &mut reader {
+        writer.write_packet(&packet.unwrap()).unwrap();
+    }
+
+    assert_eq!(original, output);
+}